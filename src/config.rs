@@ -2,28 +2,86 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
-// 1. 定义需要保存到文件中的数据结构
+// 1. 定义单个连接配置（一个名字 + 今天的四个字段）
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Config {
+pub struct Profile {
+    pub name: String,
     pub host: String,
     pub ports_str: String,
-    pub close_ports_str: String, // 新增：关闭端口序列
+    pub close_ports_str: String, // 关闭端口序列
     pub delay: u64,
 }
 
-// 2. 为 Config 实现一个默认值
-impl Default for Config {
+impl Default for Profile {
     fn default() -> Self {
         Self {
+            name: "默认".to_string(),
             host: "127.0.0.1".to_string(),
             ports_str: "5000, 6000, 7000".to_string(),
-            close_ports_str: "7000, 6000, 5000".to_string(), // 新增：默认的关闭序列
+            close_ports_str: "7000, 6000, 5000".to_string(), // 默认的关闭序列
             delay: 1000,
         }
     }
 }
 
-// 3. 找到配置文件的路径
+// 当前配置结构的版本号。每次字段发生不兼容改动时 +1，
+// 并在 `migrate` 中补上对应的升级步骤。
+pub const CURRENT_VERSION: u32 = 1;
+
+// 2. 定义需要保存到文件中的数据结构：一组命名的连接配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    // 早于版本化的文件没有该字段，按 0 处理后再迁移
+    #[serde(default)]
+    pub version: u32,
+    pub profiles: Vec<Profile>,
+    pub selected: usize,
+}
+
+// 3. 为 Config 实现一个默认值：只包含一个默认配置
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CURRENT_VERSION,
+            profiles: vec![Profile::default()],
+            selected: 0,
+        }
+    }
+}
+
+// 旧版本（单配置）文件的结构，仅用于迁移
+#[derive(Debug, Deserialize)]
+struct LegacyConfig {
+    host: String,
+    ports_str: String,
+    close_ports_str: String,
+    delay: u64,
+}
+
+impl Config {
+    // 把旧版本的结构逐步升级到当前版本（目前只需抬升版本号）。
+    // 返回 true 表示结构发生了变化、需要回写文件。
+    fn migrate(&mut self) -> bool {
+        if self.version >= CURRENT_VERSION {
+            return false;
+        }
+        // 未来新增字段（如更多协议默认值）时，在这里按版本补齐
+        self.version = CURRENT_VERSION;
+        true
+    }
+
+    // 保证 `selected` 始终落在一个有效的配置上
+    pub fn normalize(&mut self) {
+        if self.profiles.is_empty() {
+            self.profiles.push(Profile::default());
+        }
+        if self.selected >= self.profiles.len() {
+            self.selected = self.profiles.len() - 1;
+        }
+    }
+}
+
+// 4. 找到配置文件的路径
 fn get_config_path() -> Option<PathBuf> {
     // 使用 directories-next 找到一个安全的、跨平台的位置
     // 例如:
@@ -34,20 +92,80 @@ fn get_config_path() -> Option<PathBuf> {
         .map(|dirs| dirs.config_dir().join("config.json"))
 }
 
-// 4. 加载配置的函数
+// 将损坏的配置文件重命名为带时间戳的备份（config.json.bak.<秒>），返回备份路径
+fn backup_corrupt_file(path: &PathBuf) -> Result<PathBuf, String> {
+    let ts = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("config.json");
+    let backup = match path.parent() {
+        Some(parent) => parent.join(format!("{}.bak.{}", file_name, ts)),
+        None => PathBuf::from(format!("{}.bak.{}", file_name, ts)),
+    };
+    fs::rename(path, &backup).map_err(|e| format!("备份配置文件失败: {}", e))?;
+    Ok(backup)
+}
+
+// 5. 加载配置的函数
 // 如果文件存在且有效，则加载。否则，创建一个默认配置并保存它。
 pub fn load_or_create() -> Config {
     if let Some(path) = get_config_path() {
         if path.exists() {
             // 文件存在，尝试读取和解析
             if let Ok(content) = fs::read_to_string(&path) {
-                if let Ok(config) = serde_json::from_str(&content) {
-                    println!("成功从 {} 加载配置", path.display());
+                if let Ok(mut config) = serde_json::from_str::<Config>(&content) {
+                    // 读到更旧的版本（含没有 version 字段的文件）时，升级后回写
+                    let upgraded = config.migrate();
+                    config.normalize();
+                    if upgraded {
+                        if let Err(e) = save(&config) {
+                            eprintln!("升级配置后保存失败：{}", e);
+                        } else {
+                            println!("已将配置升级到版本 {}", CURRENT_VERSION);
+                        }
+                    } else {
+                        println!("成功从 {} 加载配置", path.display());
+                    }
                     return config;
                 }
+                // 尝试把旧的单配置文件迁移成只有一个元素的配置列表
+                if let Ok(legacy) = serde_json::from_str::<LegacyConfig>(&content) {
+                    let config = Config {
+                        version: CURRENT_VERSION,
+                        profiles: vec![Profile {
+                            name: "默认".to_string(),
+                            host: legacy.host,
+                            ports_str: legacy.ports_str,
+                            close_ports_str: legacy.close_ports_str,
+                            delay: legacy.delay,
+                        }],
+                        selected: 0,
+                    };
+                    if let Err(e) = save(&config) {
+                        eprintln!("迁移旧配置后保存失败：{}", e);
+                    } else {
+                        println!("已将旧版配置迁移为配置列表并保存到 {}", path.display());
+                    }
+                    return config;
+                }
+            }
+            // 无法解析：先把原文件备份到带时间戳的 .bak，避免直接覆盖用户的真实设置
+            match backup_corrupt_file(&path) {
+                Ok(backup) => eprintln!(
+                    "配置文件 {} 无法解析，已备份到 {}，将写入默认配置。",
+                    path.display(),
+                    backup.display()
+                ),
+                Err(e) => eprintln!(
+                    "配置文件 {} 无法解析，且备份失败（{}），将写入默认配置。",
+                    path.display(),
+                    e
+                ),
             }
-            // 如果读取或解析失败，则打印错误并使用默认值
-            eprintln!("配置文件 {} 损坏，使用默认配置。", path.display());
         }
 
         // 文件不存在或损坏，使用默认值并尝试保存
@@ -65,7 +183,25 @@ pub fn load_or_create() -> Config {
     }
 }
 
-// 5. 保存配置的函数
+// 将配置导出到用户选择的任意路径（与固定的 get_config_path 位置无关）
+pub fn export_to(path: &std::path::Path, config: &Config) -> Result<(), String> {
+    let content =
+        serde_json::to_string_pretty(config).map_err(|e| format!("序列化配置失败: {}", e))?;
+    fs::write(path, content).map_err(|e| format!("写入配置文件失败: {}", e))?;
+    Ok(())
+}
+
+// 从用户选择的任意路径导入配置，并做一次版本迁移与规整
+pub fn import_from(path: &std::path::Path) -> Result<Config, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let mut config: Config =
+        serde_json::from_str(&content).map_err(|e| format!("解析配置文件失败: {}", e))?;
+    config.migrate();
+    config.normalize();
+    Ok(config)
+}
+
+// 6. 保存配置的函数
 // 这个函数会返回一个 Result，以便我们可以向用户显示成功或失败的消息。
 pub fn save(config: &Config) -> Result<(), String> {
     let path = get_config_path().ok_or_else(|| "无法找到配置目录".to_string())?;
@@ -85,3 +221,52 @@ pub fn save(config: &Config) -> Result<(), String> {
     println!("配置已成功保存到 {}", path.display());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_single_profile_json_parses_as_legacy() {
+        let legacy = r#"{"host":"10.0.0.1","ports_str":"1,2,3","close_ports_str":"3,2,1","delay":500}"#;
+        // 旧文件缺少 profiles/selected，无法直接当作新的 Config 解析
+        assert!(serde_json::from_str::<Config>(legacy).is_err());
+        // 但能解析成 LegacyConfig，供 load_or_create 迁移成单元素配置列表
+        let parsed: LegacyConfig = serde_json::from_str(legacy).unwrap();
+        assert_eq!(parsed.host, "10.0.0.1");
+        assert_eq!(parsed.close_ports_str, "3,2,1");
+        assert_eq!(parsed.delay, 500);
+    }
+
+    #[test]
+    fn config_without_version_field_migrates_and_bumps_version() {
+        let json = r#"{"profiles":[{"name":"默认","host":"127.0.0.1","ports_str":"1","close_ports_str":"1","delay":1000}],"selected":0}"#;
+        let mut config: Config = serde_json::from_str(json).unwrap();
+        // 没有 version 字段时默认为 0
+        assert_eq!(config.version, 0);
+        // 迁移后版本号被抬升到当前版本
+        assert!(config.migrate());
+        assert_eq!(config.version, CURRENT_VERSION);
+        assert_eq!(config.profiles.len(), 1);
+        // 已是最新版本时不再需要迁移
+        assert!(!config.migrate());
+    }
+
+    #[test]
+    fn default_config_needs_no_migration() {
+        let mut config = Config::default();
+        assert!(!config.migrate());
+    }
+
+    #[test]
+    fn normalize_clamps_selected_and_ensures_one_profile() {
+        let mut config = Config {
+            version: CURRENT_VERSION,
+            profiles: Vec::new(),
+            selected: 7,
+        };
+        config.normalize();
+        assert_eq!(config.profiles.len(), 1);
+        assert_eq!(config.selected, 0);
+    }
+}
@@ -0,0 +1,209 @@
+use async_std::future::timeout;
+use async_std::net::ToSocketAddrs;
+use async_std::net::{TcpStream, UdpSocket};
+use async_std::task::sleep;
+use std::time::Duration;
+
+// 敲门时使用的协议：knockd 之类的守护进程会区分 TCP / UDP 包
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Proto {
+    #[default]
+    Tcp,
+    Udp,
+}
+
+impl Proto {
+    // 用于在提示信息中展示协议名
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Proto::Tcp => "tcp",
+            Proto::Udp => "udp",
+        }
+    }
+}
+
+// 一次敲门的结果，区分三种情况以便如实告知用户（而不是把超时也报成成功）：
+// - Delivered：TCP 连接成功或被拒绝 / UDP 数据报已发出，报文确实送达目标
+// - Timeout：connect 超时，SYN 已发出但无响应（被防火墙过滤的主机属正常现象）
+// - Failed：明确的错误（网络不可达、UDP 绑定或发送失败等）
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    Delivered,
+    Timeout,
+    Failed(String),
+}
+
+impl Outcome {
+    // 只有明确的错误才算失败；超时按“已送达但无响应”处理，不计入失败
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Outcome::Failed(_))
+    }
+}
+
+// 敲门序列中单个端口的结果
+#[derive(Debug, Clone)]
+pub struct StepResult {
+    pub port: u16,
+    pub proto: Proto,
+    pub outcome: Outcome,
+}
+
+// 解析以逗号分隔的端口序列，支持 `5000/udp, 6000/tcp, 7000` 形式的协议后缀；
+// 省略后缀时默认为 TCP。遇到非法端口或协议时返回错误，GUI 与无界面（CLI）模式共用。
+pub fn parse_port_str(port_str: &str) -> Result<Vec<(u16, Proto)>, String> {
+    let mut parsed_ports = Vec::new();
+    for (i, port) in port_str.split(',').enumerate() {
+        let trimmed = port.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let (num_str, proto) = match trimmed.split_once('/') {
+            Some((num, suffix)) => {
+                let proto = match suffix.trim().to_ascii_lowercase().as_str() {
+                    "tcp" => Proto::Tcp,
+                    "udp" => Proto::Udp,
+                    other => {
+                        return Err(format!("第 {} 个端口协议无法识别：'{}'", i + 1, other));
+                    }
+                };
+                (num.trim(), proto)
+            }
+            None => (trimmed, Proto::default()),
+        };
+        let num = num_str
+            .parse::<u16>()
+            .map_err(|e| format!("第 {} 个端口解析失败：'{}' ({})", i + 1, num_str, e))?;
+        parsed_ports.push((num, proto));
+    }
+    Ok(parsed_ports)
+}
+
+// 可被 GUI 与 CLI 复用的敲门序列执行逻辑：
+// 先解析一次主机名（失败则中止整条序列并返回 Err），再依次对每个端口 shoot + 延迟，
+// 把每一步的状态打印到标准输出，并收集逐端口结果返回给调用方。
+// 单个端口失败不会中止整条序列（被防火墙过滤的主机本就是常见情形）。
+pub async fn run_sequence(
+    host: &str,
+    ports: &[(u16, Proto)],
+    delay: u64,
+) -> Result<Vec<StepResult>, String> {
+    resolve(host).await?;
+    let total = ports.len();
+    let mut results = Vec::with_capacity(total);
+    for (i, &(port, proto)) in ports.iter().enumerate() {
+        let addr = format!("{}:{}", host, port);
+        let outcome = shoot(addr, proto).await;
+        match &outcome {
+            Outcome::Delivered => println!(
+                "[{}/{}] {}:{}/{} 敲门已送达",
+                i + 1,
+                total,
+                host,
+                port,
+                proto.as_str()
+            ),
+            // 超时不计入失败：SYN 已发出，被过滤的主机本就不会回应
+            Outcome::Timeout => println!(
+                "[{}/{}] {}:{}/{} 已发送，未收到响应（可能被防火墙过滤）",
+                i + 1,
+                total,
+                host,
+                port,
+                proto.as_str()
+            ),
+            Outcome::Failed(e) => println!(
+                "[{}/{}] {}:{}/{} 敲门失败：{}",
+                i + 1,
+                total,
+                host,
+                port,
+                proto.as_str(),
+                e
+            ),
+        }
+        results.push(StepResult {
+            port,
+            proto,
+            outcome,
+        });
+        // 最后一个端口之后不再等待
+        if i + 1 < total {
+            sleep(Duration::from_millis(delay)).await;
+        }
+    }
+    Ok(results)
+}
+
+// 在发送序列前先解析一次主机名，失败时直接中止整个敲门流程
+pub async fn resolve(host: &str) -> Result<(), String> {
+    let target = format!("{}:0", host);
+    match target.to_socket_addrs().await {
+        Ok(mut addrs) => {
+            if addrs.next().is_some() {
+                Ok(())
+            } else {
+                Err(format!("无法解析主机 '{}'", host))
+            }
+        }
+        Err(e) => Err(format!("无法解析主机 '{}': {}", host, e)),
+    }
+}
+
+// 向目标端口发送一次敲门，并把底层套接字的结果归类为 `Outcome`：
+// - TCP 分支尝试一次带超时的 connect；连接成功或被拒绝都说明 SYN 已送达
+// - 被防火墙静默丢弃时会超时：SYN 确实发出了，但得不到响应，单列为 Timeout 告知用户
+// - UDP 分支绑定一个临时端口并发送一个零长度数据报
+pub async fn shoot(addr: String, proto: Proto) -> Outcome {
+    match proto {
+        Proto::Tcp => match timeout(Duration::from_secs(1), TcpStream::connect(&addr)).await {
+            Ok(Ok(_)) => Outcome::Delivered,
+            // 连接被拒绝说明 SYN 已经到达目标，敲门依然有效
+            Ok(Err(e)) if e.kind() == std::io::ErrorKind::ConnectionRefused => Outcome::Delivered,
+            Ok(Err(e)) => Outcome::Failed(e.to_string()),
+            Err(_) => Outcome::Timeout,
+        },
+        Proto::Udp => match UdpSocket::bind("0.0.0.0:0").await {
+            Ok(socket) => match socket.send_to(&[], &addr).await {
+                Ok(_) => Outcome::Delivered,
+                Err(e) => Outcome::Failed(e.to_string()),
+            },
+            Err(e) => Outcome::Failed(e.to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_mixed_protocols() {
+        let ports = parse_port_str("5000/udp, 6000/tcp, 7000").unwrap();
+        assert_eq!(
+            ports,
+            vec![(5000, Proto::Udp), (6000, Proto::Tcp), (7000, Proto::Tcp)]
+        );
+    }
+
+    #[test]
+    fn omitted_suffix_defaults_to_tcp() {
+        assert_eq!(parse_port_str("8080").unwrap(), vec![(8080, Proto::Tcp)]);
+    }
+
+    #[test]
+    fn ignores_empty_entries() {
+        assert_eq!(parse_port_str("5000, ,6000").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn rejects_bad_port() {
+        assert!(parse_port_str("not_a_port").is_err());
+        // 超出 u16 范围
+        assert!(parse_port_str("70000").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_protocol() {
+        assert!(parse_port_str("5000/sctp").is_err());
+    }
+}
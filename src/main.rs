@@ -1,15 +1,15 @@
 mod config;
 mod knock;
 
-use async_std::task::sleep;
 use iced::event::{self, Event};
 use iced::window;
-use knock::shoot;
+use knock::{run_sequence, Outcome, Proto, StepResult};
 
-use iced::widget::{button, column, text_input};
-use iced::{Alignment, Element, Font, Subscription, Task};
+use iced::widget::{button, column, pick_list, text, text_input};
+use iced::{Alignment, Element, Font, Length, Subscription, Task};
+use iced_aw::menu::{Item, Menu};
+use iced_aw::menu_bar;
 use iced_toasts::{toast, toast_container, ToastContainer, ToastId, ToastLevel};
-use std::time::Duration;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum KnockType {
@@ -17,17 +17,32 @@ enum KnockType {
     Close,
 }
 
+// pick_list 中展示的配置项：名字用于显示，索引用于定位到 `profiles` 中的条目
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ProfileChoice {
+    index: usize,
+    name: String,
+}
+
+impl std::fmt::Display for ProfileChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
 #[derive(Debug)]
 struct PandaKnocking<'a, Message> {
     host: String,
     ports_str: String,
-    ports: Vec<u16>,
+    ports: Vec<(u16, Proto)>,
     close_ports_str: String,
-    close_ports: Vec<u16>,
+    close_ports: Vec<(u16, Proto)>,
     toasts: ToastContainer<'a, Message>,
     delay: u64,
     is_knocking: bool,        // 新增状态：跟踪敲门 过程是否正在进行
     is_close_requested: bool, // 是否用户点击了关闭按钮
+    profiles: Vec<config::Profile>, // 所有命名的连接配置
+    selected: usize,          // 当前选中的配置索引
 }
 
 #[derive(Debug, Clone)]
@@ -38,15 +53,25 @@ enum Message {
     ClosePortInputChanged(String),
     DismissToast(ToastId),
     PushToast((String, ToastLevel)),
-    // 新增消息：用于处理每一步的敲门操作
-    // usize 参数是下一个要敲门的端口的索引
-    KnockStep((KnockType, usize)),
+    // 新增消息：整条敲门序列（由 knock::run_sequence 执行）结束后送回逐端口结果，
+    // Err 表示主机名解析失败、序列未能启动
+    SequenceDone((KnockType, Result<Vec<StepResult>, String>)),
+    // 新增消息：切换当前选中的连接配置
+    ProfileSelected(usize),
+    // 新增消息：新建 / 删除配置
+    NewProfile,
+    DeleteProfile,
     SaveButtonPressed,
     KnockPressed,
     CloseKnockPressed,
     EventOccurred(Event),
     ExitApp,
     SaveCompleted(Result<(), String>), // 用于接收保存任务的结果
+    // 菜单栏：导出 / 导入配置到任意路径
+    ExportConfig,
+    ImportConfig,
+    ConfigExported(Result<Option<String>, String>), // Ok(None) 表示用户取消
+    ConfigImported(Result<Option<config::Config>, String>),
 }
 
 impl Default for PandaKnocking<'_, Message> {
@@ -58,18 +83,24 @@ impl Default for PandaKnocking<'_, Message> {
 impl PandaKnocking<'_, Message> {
     fn new() -> Self {
         // 从文件加载配置，如果失败则使用默认值
-        let config = config::load_or_create();
+        let mut config = config::load_or_create();
+        config.normalize();
+
+        // 用当前选中的配置填充可编辑字段
+        let current = config.profiles[config.selected].clone();
 
         let mut app = Self {
             toasts: toast_container(Message::DismissToast),
-            host: config.host,
-            ports_str: config.ports_str,
+            host: current.host,
+            ports_str: current.ports_str,
             ports: Vec::new(), // 将在下面解析
             close_ports: Vec::new(),
-            close_ports_str: config.close_ports_str,
-            delay: config.delay,
+            close_ports_str: current.close_ports_str,
+            delay: current.delay,
             is_knocking: false,
             is_close_requested: false,
+            selected: config.selected,
+            profiles: config.profiles,
         };
 
         // 确保 `ports` 向量与加载的 `ports_str` 同步
@@ -78,6 +109,44 @@ impl PandaKnocking<'_, Message> {
         app
     }
 
+    // 把当前可编辑字段写回到选中的配置里
+    fn sync_selected_profile(&mut self) {
+        if let Some(profile) = self.profiles.get_mut(self.selected) {
+            profile.host = self.host.clone();
+            profile.ports_str = self.ports_str.clone();
+            profile.close_ports_str = self.close_ports_str.clone();
+            profile.delay = self.delay;
+        }
+    }
+
+    // 用某个配置的内容填充可编辑字段，并重新解析端口
+    fn load_profile(&mut self, index: usize) {
+        if let Some(profile) = self.profiles.get(index).cloned() {
+            self.selected = index;
+            self.host = profile.host;
+            self.ports_str = profile.ports_str;
+            self.close_ports_str = profile.close_ports_str;
+            self.delay = profile.delay;
+            self.parse_ports();
+            self.parse_close_ports();
+        }
+    }
+
+    // 用 knock::run_sequence 在后台执行一整条敲门序列，完成后通过 SequenceDone 回收结果。
+    // 开启与关闭、GUI 与 CLI 都走同一套序列逻辑。
+    fn start_sequence(&self, knock_type: KnockType) -> Task<Message> {
+        let host = self.host.clone();
+        let ports = match knock_type {
+            KnockType::Open => self.ports.clone(),
+            KnockType::Close => self.close_ports.clone(),
+        };
+        let delay = self.delay;
+        Task::perform(
+            async move { (knock_type, run_sequence(&host, &ports, delay).await) },
+            Message::SequenceDone,
+        )
+    }
+
     fn parse_ports(&mut self) {
         self.ports = self.parse_port_str(&self.ports_str.clone(), "开启");
     }
@@ -88,28 +157,18 @@ impl PandaKnocking<'_, Message> {
     }
 
     // 通用端口解析逻辑
-    fn parse_port_str(&mut self, port_str: &str, kind: &str) -> Vec<u16> {
-        let mut parsed_ports = Vec::new();
-        for (i, port) in port_str.split(',').enumerate() {
-            let trimmed = port.trim();
-            if trimmed.is_empty() {
-                continue;
-            } // 忽略空字符串
-            match trimmed.parse::<u16>() {
-                Ok(num) => parsed_ports.push(num),
-                Err(err) => {
-                    let msg = format!(
-                        "第 {} 个{}端口解析失败：'{}' ({})",
-                        i + 1,
-                        kind,
-                        trimmed,
-                        err
-                    );
-                    self.toasts.push(toast(&msg).level(ToastLevel::Error));
-                }
+    // 支持在端口后追加协议后缀，例如 `5000/udp, 6000/tcp, 7000`；
+    // 省略后缀时默认为 TCP。解析规则统一交由 `knock::parse_port_str`，
+    // 出错时把错误信息以 toast 呈现，避免两处解析逻辑产生分歧。
+    fn parse_port_str(&mut self, port_str: &str, kind: &str) -> Vec<(u16, Proto)> {
+        match knock::parse_port_str(port_str) {
+            Ok(ports) => ports,
+            Err(e) => {
+                let msg = format!("{}端口解析失败：{}", kind, e);
+                self.toasts.push(toast(&msg).level(ToastLevel::Error));
+                Vec::new()
             }
         }
-        parsed_ports
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
@@ -158,77 +217,123 @@ impl PandaKnocking<'_, Message> {
 
                 // 设置状态为正在敲门，这将禁用按钮
                 self.is_knocking = true;
-
-                // 直接发送第一个步骤的消息来启动任务链
-                Task::perform(async move { (KnockType::Open, 0) }, Message::KnockStep)
+                // GUI 与 CLI 共用 knock::run_sequence 驱动整条序列
+                self.start_sequence(KnockType::Open)
             }
             Message::CloseKnockPressed => {
                 if self.is_knocking || self.close_ports.is_empty() {
                     return Task::none();
                 }
                 self.is_knocking = true;
-                // --- 修改：启动“关闭”序列的第一步 ---
-                Task::perform(async move { (KnockType::Close, 0) }, Message::KnockStep)
+                self.start_sequence(KnockType::Close)
             }
-            Message::KnockStep(data) => {
-                let (knock_type, index) = data;
-                let (ports_to_use, kind_str) = match knock_type {
-                    KnockType::Open => (&self.ports, "开启"),
-                    KnockType::Close => (&self.close_ports, "关闭"),
+            Message::SequenceDone((knock_type, result)) => {
+                self.is_knocking = false;
+                let kind_str = match knock_type {
+                    KnockType::Open => "开启",
+                    KnockType::Close => "关闭",
                 };
 
-                if index > 0 {
-                    let prev_index = index - 1;
-                    if let Some(&port) = ports_to_use.get(prev_index) {
-                        let msg = format!(
-                            "第 {} 个{}端口成功发送敲门指令：'{}:{}'",
-                            prev_index + 1,
-                            kind_str,
-                            self.host,
-                            port
-                        );
-                        self.toasts.push(toast(&msg).level(ToastLevel::Success));
+                match result {
+                    Ok(results) => {
+                        // 逐端口如实汇报结果
+                        for (i, step) in results.iter().enumerate() {
+                            let (msg, level) = match &step.outcome {
+                                Outcome::Delivered => (
+                                    format!(
+                                        "第 {} 个{}端口敲门已送达：'{}:{}/{}'",
+                                        i + 1,
+                                        kind_str,
+                                        self.host,
+                                        step.port,
+                                        step.proto.as_str()
+                                    ),
+                                    ToastLevel::Success,
+                                ),
+                                // 超时如实告知用户，但不按错误处理
+                                Outcome::Timeout => (
+                                    format!(
+                                        "第 {} 个{}端口已发送但未收到响应（可能被防火墙过滤）：'{}:{}/{}'",
+                                        i + 1,
+                                        kind_str,
+                                        self.host,
+                                        step.port,
+                                        step.proto.as_str()
+                                    ),
+                                    ToastLevel::Info,
+                                ),
+                                Outcome::Failed(e) => (
+                                    format!(
+                                        "第 {} 个{}端口敲门失败：'{}:{}/{}' ({})",
+                                        i + 1,
+                                        kind_str,
+                                        self.host,
+                                        step.port,
+                                        step.proto.as_str(),
+                                        e
+                                    ),
+                                    ToastLevel::Error,
+                                ),
+                            };
+                            self.toasts.push(toast(&msg).level(level));
+                        }
+                        self.toasts
+                            .push(toast(&format!("所有{}端口敲门完成!", kind_str)).level(ToastLevel::Info));
+                    }
+                    Err(e) => {
+                        // 主机名解析失败等导致序列未启动
+                        self.toasts.push(toast(&e).level(ToastLevel::Error));
                     }
                 }
 
-                if let Some(&port) = ports_to_use.get(index) {
-                    let host_clone = self.host.clone();
-                    let delay = self.delay;
-                    Task::perform(
-                        async move {
-                            let addr = format!("{}:{}", host_clone, port);
-                            shoot(addr).await;
-                            sleep(Duration::from_millis(delay)).await;
-                            (knock_type, index + 1)
-                        },
-                        |(ty, idx)| Message::KnockStep((ty, idx)),
-                    )
-                } else {
-                    self.is_knocking = false;
-                    let msg = format!("所有{}端口敲门完成!", kind_str);
-                    self.toasts.push(toast(&msg).level(ToastLevel::Info));
-
-                    // 如果是“关闭”序列结束 → 自动退出程序
-                    if let KnockType::Close = knock_type {
-                        if self.is_close_requested {
-                            return Task::perform(async {}, |_| Message::ExitApp);
-                        }
+                // 如果是“关闭”序列（含解析失败的情况）且由关闭请求触发 → 自动退出程序，
+                // 否则主机无法解析时窗口将永远关不掉
+                if let KnockType::Close = knock_type {
+                    if self.is_close_requested {
+                        return Task::perform(async {}, |_| Message::ExitApp);
                     }
-
-                    Task::none()
                 }
+
+                Task::none()
             }
             Message::DismissToast(id) => {
                 self.toasts.dismiss(id);
                 Task::none()
             }
+            Message::ProfileSelected(index) => {
+                // 切换配置前，先把当前编辑内容保存回原配置
+                self.sync_selected_profile();
+                self.load_profile(index);
+                Task::none()
+            }
+            Message::NewProfile => {
+                self.sync_selected_profile();
+                let profile = config::Profile {
+                    name: format!("配置 {}", self.profiles.len() + 1),
+                    ..config::Profile::default()
+                };
+                self.profiles.push(profile);
+                self.load_profile(self.profiles.len() - 1);
+                Task::none()
+            }
+            Message::DeleteProfile => {
+                if self.profiles.len() <= 1 {
+                    self.toasts
+                        .push(toast("至少需要保留一个配置").level(ToastLevel::Error));
+                    return Task::none();
+                }
+                self.profiles.remove(self.selected);
+                let next = self.selected.min(self.profiles.len() - 1);
+                self.load_profile(next);
+                Task::none()
+            }
             Message::SaveButtonPressed => {
-                // 从当前状态创建一个 config 对象
+                // 先把当前编辑内容写回选中的配置，再保存整个配置列表
+                self.sync_selected_profile();
                 let config_to_save = config::Config {
-                    host: self.host.clone(),
-                    ports_str: self.ports_str.clone(),
-                    close_ports_str: self.close_ports_str.clone(),
-                    delay: self.delay,
+                    version: config::CURRENT_VERSION,
+                    profiles: self.profiles.clone(),
+                    selected: self.selected,
                 };
 
                 // 创建一个异步任务来保存配置
@@ -252,6 +357,75 @@ impl PandaKnocking<'_, Message> {
                 }
                 Task::none()
             }
+            Message::ExportConfig => {
+                // 先把当前编辑内容写回配置，再弹出原生保存对话框
+                self.sync_selected_profile();
+                let cfg = config::Config {
+                    version: config::CURRENT_VERSION,
+                    profiles: self.profiles.clone(),
+                    selected: self.selected,
+                };
+                Task::perform(
+                    async move {
+                        if let Some(handle) = rfd::AsyncFileDialog::new()
+                            .add_filter("JSON", &["json"])
+                            .set_file_name("config.json")
+                            .save_file()
+                            .await
+                        {
+                            let path = handle.path().to_path_buf();
+                            config::export_to(&path, &cfg)
+                                .map(|_| Some(path.display().to_string()))
+                        } else {
+                            Ok(None)
+                        }
+                    },
+                    Message::ConfigExported,
+                )
+            }
+            Message::ImportConfig => Task::perform(
+                async move {
+                    if let Some(handle) = rfd::AsyncFileDialog::new()
+                        .add_filter("JSON", &["json"])
+                        .pick_file()
+                        .await
+                    {
+                        config::import_from(handle.path()).map(Some)
+                    } else {
+                        Ok(None)
+                    }
+                },
+                Message::ConfigImported,
+            ),
+            Message::ConfigExported(result) => {
+                match result {
+                    Ok(Some(path)) => self
+                        .toasts
+                        .push(toast(format!("配置已导出到 {}", path).as_str()).level(ToastLevel::Success)),
+                    Ok(None) => {} // 用户取消，无需提示
+                    Err(e) => self
+                        .toasts
+                        .push(toast(format!("导出配置失败: {}", e).as_str()).level(ToastLevel::Error)),
+                }
+                Task::none()
+            }
+            Message::ConfigImported(result) => {
+                match result {
+                    Ok(Some(mut config)) => {
+                        config.normalize();
+                        self.profiles = config.profiles;
+                        // 用导入后选中的配置重新填充并解析界面字段
+                        self.load_profile(config.selected);
+                        self.toasts
+                            .push(toast("配置导入成功!").level(ToastLevel::Success));
+                    }
+                    Ok(None) => {} // 用户取消，无需提示
+                    Err(e) => self
+                        .toasts
+                        .push(toast(format!("导入配置失败: {}", e).as_str()).level(ToastLevel::Error)),
+                }
+                Task::none()
+            }
             Message::EventOccurred(event) => {
                 if let Event::Window(window::Event::CloseRequested) = event {
                     // 如果当前正在敲门或已经在关闭，则忽略
@@ -270,9 +444,9 @@ impl PandaKnocking<'_, Message> {
                         return Task::perform(async {}, |_| Message::ExitApp);
                     }
 
-                    // 启动关闭敲门序列
+                    // 启动关闭敲门序列；序列结束（或解析失败）后 SequenceDone 会负责退出
                     self.is_knocking = true;
-                    return Task::perform(async move { (KnockType::Close, 0) }, Message::KnockStep);
+                    return self.start_sequence(KnockType::Close);
                 };
                 Task::none()
             }
@@ -283,6 +457,66 @@ impl PandaKnocking<'_, Message> {
     }
 
     fn view<'a>(&'a self) -> Element<'a, Message> {
+        // 顶部菜单栏：File / Tools / About（参考串口调试助手的布局）
+        let menu_tpl = |items| Menu::new(items).max_width(180.0).offset(0.0).spacing(4.0);
+        // 下拉菜单里的条目统一做成占满宽度的按钮
+        let entry = |label, msg| {
+            Item::new(
+                button(text(label))
+                    .width(Length::Fill)
+                    .on_press(msg),
+            )
+        };
+        let menu = menu_bar!(
+            (button(text("File")), menu_tpl(vec![
+                entry("导出配置…", Message::ExportConfig),
+                entry("导入配置…", Message::ImportConfig),
+                // 走与窗口关闭 (X) 相同的流程，先发送关闭端口序列再退出，
+                // 不能直接 ExitApp，否则会把防火墙端口留在打开状态
+                entry(
+                    "退出",
+                    Message::EventOccurred(Event::Window(window::Event::CloseRequested)),
+                ),
+            ]))
+            (button(text("Tools")), menu_tpl(vec![
+                entry("保存配置", Message::SaveButtonPressed),
+            ]))
+            (button(text("About")), menu_tpl(vec![
+                entry("熊猫端口敲门器", Message::PushToast((
+                    format!("熊猫端口敲门器 · 配置版本 {}", config::CURRENT_VERSION),
+                    ToastLevel::Info,
+                ))),
+            ]))
+        );
+
+        // 配置选择下拉框：列出所有配置的名字，选中后切换可编辑字段
+        let choices: Vec<ProfileChoice> = self
+            .profiles
+            .iter()
+            .enumerate()
+            .map(|(index, profile)| ProfileChoice {
+                index,
+                name: profile.name.clone(),
+            })
+            .collect();
+        let selected_choice = choices.get(self.selected).cloned();
+        let profile_picker = pick_list(choices, selected_choice, |choice| {
+            Message::ProfileSelected(choice.index)
+        })
+        .padding(10);
+
+        let new_profile_button = button("新建").on_press(Message::NewProfile).padding(10);
+        let delete_profile_button = button("删除配置")
+            .on_press(Message::DeleteProfile)
+            .padding(10);
+        let profile_bar = iced::widget::row![
+            profile_picker,
+            new_profile_button,
+            delete_profile_button
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
         let host_input = text_input("请输入ip...", &self.host.as_str())
             .on_input(Message::HostInputChanged)
             .padding(10)
@@ -326,7 +560,7 @@ impl PandaKnocking<'_, Message> {
             .spacing(10)
             .align_y(Alignment::Center);
 
-        let interface = column![form, buttons]
+        let interface = column![menu, profile_bar, form, buttons]
             .align_x(Alignment::Center)
             .spacing(20);
         self.toasts.view(interface)
@@ -337,7 +571,86 @@ impl PandaKnocking<'_, Message> {
     }
 }
 
+// 无界面（CLI）模式：解析命令行参数，在 async-std 运行时上执行一次敲门序列后退出。
+// 典型用法：`panda-knock --host 10.0.0.1 --open 5000,6000,7000 --delay 800`，
+// 或使用 `--close` 触发关闭序列。
+fn run_cli(args: &[String]) -> ! {
+    let mut host: Option<String> = None;
+    let mut ports_str: Option<String> = None;
+    let mut delay: u64 = 1000;
+
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--host" => host = iter.next().cloned(),
+            // --open 与 --close 都只是一串端口，交给同一套序列逻辑执行
+            "--open" | "--close" => ports_str = iter.next().cloned(),
+            "--delay" => {
+                match iter.next().map(|v| v.trim().parse::<u64>()) {
+                    Some(Ok(num)) => delay = num,
+                    _ => {
+                        eprintln!("--delay 需要一个合法的毫秒数");
+                        std::process::exit(2);
+                    }
+                }
+            }
+            other => {
+                eprintln!("未知参数：{}", other);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let host = match host {
+        Some(h) => h,
+        None => {
+            eprintln!("缺少 --host 参数");
+            std::process::exit(2);
+        }
+    };
+    let ports = match ports_str {
+        Some(s) => match knock::parse_port_str(&s) {
+            Ok(ports) => ports,
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+        },
+        None => {
+            eprintln!("缺少 --open 或 --close 参数");
+            std::process::exit(2);
+        }
+    };
+
+    match async_std::task::block_on(knock::run_sequence(&host, &ports, delay)) {
+        Ok(results) => {
+            // 超时不算失败（被过滤的主机本就不会回应），只有明确错误才影响退出码
+            let failed = results.iter().filter(|r| r.outcome.is_failure()).count();
+            if failed == 0 {
+                println!("敲门序列执行完成");
+                std::process::exit(0);
+            } else {
+                eprintln!("{} 个端口敲门失败", failed);
+                std::process::exit(1);
+            }
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
 fn main() -> iced::Result {
+    // 只要出现任一敲门相关参数，就进入无界面模式，不打开 GUI
+    let args: Vec<String> = std::env::args().collect();
+    if args
+        .iter()
+        .any(|a| matches!(a.as_str(), "--host" | "--open" | "--close" | "--delay"))
+    {
+        run_cli(&args);
+    }
+
     #[cfg(target_os = "windows")]
     let default_font = Font::with_name("Microsoft YaHei UI");
 